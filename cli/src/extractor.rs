@@ -1,3 +1,8 @@
+use crate::hash::content_hash;
+use crate::index;
+use crate::schema;
+use crate::tm::TranslationMemory;
+use crate::Format;
 use anyhow::Result;
 use binrw::{io::Cursor, BinRead};
 use csv::Writer;
@@ -5,13 +10,19 @@ use encoding_rs::WINDOWS_1252;
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+/// Mirrors the on-disk 16-byte record header exactly as [`crate::injector::RecordHeader`]
+/// does: name, size, and the 8 bytes of unknown/flags metadata that precede a record's data.
 #[derive(BinRead, Debug)]
 struct RecordHeader {
     #[br(map = |x: [u8; 4]| String::from_utf8_lossy(&x).into_owned())]
     name: String,
     size: u32,
+    #[allow(dead_code)]
+    unknown: u32,
+    #[allow(dead_code)]
+    flags: u32,
 }
 
 #[derive(BinRead, Debug)]
@@ -27,145 +38,136 @@ struct CsvRow<'a> {
     record_type: &'a str,
     subrecord_type: &'a str,
     original_text: String,
-    translated_text: &'static str,
+    translated_text: String,
+    /// "exact", "fuzzy", or "" if `--tm` found no prior translation for this string.
+    tm_match: &'static str,
 }
 
-lazy_static::lazy_static! {
-    static ref TRANSLATABLE_SUBRECORDS: HashMap<&'static str, HashSet<&'static str>> = {
-        let mut m = HashMap::new();
-        m.insert("ACTI", ["FNAM"].iter().cloned().collect());
-        m.insert("ALCH", ["FNAM"].iter().cloned().collect());
-        m.insert("APPA", ["FNAM"].iter().cloned().collect());
-        m.insert("ARMO", ["FNAM"].iter().cloned().collect());
-        m.insert("BODY", ["FNAM"].iter().cloned().collect());
-        m.insert("BOOK", ["FNAM", "TEXT"].iter().cloned().collect());
-        m.insert("BSGN", ["FNAM", "DESC"].iter().cloned().collect());
-        m.insert("CLAS", ["FNAM", "DESC"].iter().cloned().collect());
-        m.insert("CLOT", ["FNAM"].iter().cloned().collect());
-        m.insert("CONT", ["FNAM"].iter().cloned().collect());
-        m.insert("CREA", ["FNAM"].iter().cloned().collect());
-        m.insert("DIAL", ["NAME"].iter().cloned().collect());
-        m.insert("DOOR", ["FNAM"].iter().cloned().collect());
-        m.insert("ENCH", ["FNAM"].iter().cloned().collect());
-        m.insert("FACT", ["FNAM"].iter().cloned().collect());
-        m.insert("GLOB", ["FNAM"].iter().cloned().collect());
-        m.insert("GMST", ["STRV"].iter().cloned().collect());
-        m.insert("INFO", ["NAME"].iter().cloned().collect());
-        m.insert("INGR", ["FNAM"].iter().cloned().collect());
-        m.insert("LEVC", ["NNAM"].iter().cloned().collect());
-        m.insert("LEVI", ["NNAM"].iter().cloned().collect());
-        m.insert("LIGH", ["FNAM"].iter().cloned().collect());
-        m.insert("LOCK", ["FNAM"].iter().cloned().collect());
-        m.insert("MGEF", ["DESC"].iter().cloned().collect());
-        m.insert("MISC", ["FNAM"].iter().cloned().collect());
-        m.insert("NPC_", ["FNAM"].iter().cloned().collect());
-        m.insert("PGRD", ["NAME"].iter().cloned().collect());
-        m.insert("PROB", ["FNAM"].iter().cloned().collect());
-        m.insert("RACE", ["FNAM", "DESC"].iter().cloned().collect());
-        m.insert("REGN", ["FNAM"].iter().cloned().collect());
-        m.insert("REPA", ["FNAM"].iter().cloned().collect());
-        m.insert("SKIL", ["DESC"].iter().cloned().collect());
-        m.insert("SNDG", ["FNAM"].iter().cloned().collect());
-        m.insert("SOUN", ["FNAM"].iter().cloned().collect());
-        m.insert("SPEL", ["FNAM"].iter().cloned().collect());
-        m.insert("SSCR", ["NAME"].iter().cloned().collect());
-        m.insert("STAT", ["FNAM"].iter().cloned().collect());
-        m.insert("WEAP", ["FNAM"].iter().cloned().collect());
-        m
-    };
-    static ref ID_SUBRECORD_CANDIDATES: Vec<&'static str> = vec!["NAME", "INAM", "CNAM", "BNAM", "ANAM", "NNAM"];
+#[derive(serde::Serialize)]
+struct JsonRow {
+    unique_id: String,
+    record_type: String,
+    subrecord_type: String,
+    original_text: String,
+    original_bytes_hex: String,
+    translated_text: String,
+    tm_match: &'static str,
 }
 
-fn decode_text(bytes: &[u8]) -> String {
-    let null_pos = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
-    let (decoded, _, _) = WINDOWS_1252.decode(&bytes[..null_pos]);
-    decoded.into_owned()
+/// The fields of a single extracted translatable string, independent of output format.
+struct ExtractedRow<'a> {
+    unique_id: &'a str,
+    record_type: &'a str,
+    subrecord_type: &'a str,
+    original_text: &'a str,
+    original_bytes: &'a [u8],
+    translated_text: &'a str,
+    tm_match: &'static str,
 }
 
-fn is_translatable_text(text: &str) -> bool {
-    let trimmed = text.trim();
-
-    if trimmed.len() < 2 {
-        return false;
-    }
+/// Writes extracted rows in either CSV (streamed) or JSON (buffered, written once) form.
+/// `Writer<File>` is boxed because its internal buffers make it much larger than the `Json`
+/// variant, which would otherwise force every `OutputWriter` to reserve that much space.
+enum OutputWriter {
+    Csv(Box<Writer<File>>),
+    Json { path: PathBuf, rows: Vec<JsonRow> },
+}
 
-    let is_numeric = trimmed
-        .chars()
-        .all(|c| c.is_digit(10) || c == '.' || c == '-' || c == '+');
-    if is_numeric && trimmed.parse::<f64>().is_ok() {
-        return false;
+impl OutputWriter {
+    fn new(output_path: &Path, format: Format) -> Result<Self> {
+        match format {
+            Format::Csv => Ok(OutputWriter::Csv(Box::new(Writer::from_path(output_path)?))),
+            Format::Json => Ok(OutputWriter::Json {
+                path: output_path.to_path_buf(),
+                rows: Vec::new(),
+            }),
+        }
     }
 
-    let script_patterns = [
-        "begin ",
-        "end\n",
-        "endif",
-        "while (",
-        "if (",
-        "else\n",
-        "getjournalindex",
-        "messagebox",
-        "additem",
-        "removeitem",
-        "startscript",
-        "stopscript",
-        "getglobal",
-        "setglobal",
-        "short ",
-        "long ",
-        "float ",
-    ];
-    let text_lower = trimmed.to_lowercase();
-    if script_patterns.iter().any(|p| text_lower.starts_with(p)) {
-        return false;
+    fn write_row(&mut self, row: ExtractedRow) -> Result<()> {
+        match self {
+            OutputWriter::Csv(wtr) => {
+                wtr.serialize(CsvRow {
+                    unique_id: row.unique_id.to_string(),
+                    record_type: row.record_type,
+                    subrecord_type: row.subrecord_type,
+                    original_text: row.original_text.to_string(),
+                    translated_text: row.translated_text.to_string(),
+                    tm_match: row.tm_match,
+                })?;
+            }
+            OutputWriter::Json { rows, .. } => {
+                rows.push(JsonRow {
+                    unique_id: row.unique_id.to_string(),
+                    record_type: row.record_type.to_string(),
+                    subrecord_type: row.subrecord_type.to_string(),
+                    original_text: row.original_text.to_string(),
+                    original_bytes_hex: to_hex(row.original_bytes),
+                    translated_text: row.translated_text.to_string(),
+                    tm_match: row.tm_match,
+                });
+            }
+        }
+        Ok(())
     }
 
-    if trimmed.contains('\n')
-        && trimmed.lines().any(|line| {
-            let line_lower = line.trim().to_lowercase();
-            line_lower.starts_with("if ")
-                || line_lower.starts_with("set ")
-                || line_lower.starts_with("short ")
-                || line_lower.starts_with("long ")
-                || line_lower.starts_with("float ")
-        })
-    {
-        return false;
+    fn finish(self) -> Result<()> {
+        match self {
+            OutputWriter::Csv(mut wtr) => Ok(wtr.flush()?),
+            OutputWriter::Json { path, rows } => {
+                let file = File::create(path)?;
+                serde_json::to_writer_pretty(file, &rows)?;
+                Ok(())
+            }
+        }
     }
+}
 
-    let code_patterns = ["==", "!=", ">=", "<=", "->", "=>", "&&", "||"];
-    if code_patterns.iter().any(|p| trimmed.contains(p)) {
-        return false;
-    }
+/// Encodes `bytes` as a lowercase hex string, for the JSON format's byte-exact round trip.
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
 
-    let punct_count = trimmed
-        .chars()
-        .filter(|&c| "{}[]()=<>!&|;".contains(c))
-        .count();
-    if punct_count > 5 && (punct_count as f32 / trimmed.len() as f32) > 0.5 {
-        return false;
+/// Pre-fills `translated_text` for `original_text` from the translation memory, if given.
+fn lookup_tm(memory: Option<&TranslationMemory>, original_text: &str) -> (String, &'static str) {
+    match memory.and_then(|m| m.lookup(original_text)) {
+        Some(entry) if entry.confirmed => (entry.translated_text, "exact"),
+        Some(entry) => (entry.translated_text, "fuzzy"),
+        None => (String::new(), ""),
     }
+}
 
-    if (trimmed.contains('\\') && trimmed.matches('\\').count() > 1)
-        || trimmed.starts_with("data\\")
-    {
-        return false;
-    }
+lazy_static::lazy_static! {
+    static ref ID_SUBRECORD_CANDIDATES: Vec<&'static str> = vec!["NAME", "INAM", "CNAM", "BNAM", "ANAM", "NNAM"];
+}
 
-    true
+fn decode_text(bytes: &[u8]) -> String {
+    let null_pos = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    let (decoded, _, _) = WINDOWS_1252.decode(&bytes[..null_pos]);
+    decoded.into_owned()
 }
 
-fn parse_subrecords(record_data: &[u8]) -> Result<HashMap<String, Vec<Vec<u8>>>> {
+/// A subrecord type's occurrences within a record, each paired with the absolute file offset
+/// of its data (not its header) and its decoded bytes.
+type SubrecordsByType = HashMap<String, Vec<(u64, Vec<u8>)>>;
+
+/// Parses subrecords, pairing each with the absolute file offset of its data (not its
+/// header), so callers can build a [`index::PluginIndex`] for seek-based access later.
+fn parse_subrecords_with_offsets(
+    record_data: &[u8],
+    record_data_abs_offset: u64,
+) -> Result<SubrecordsByType> {
     let mut sub_records = HashMap::new();
     let mut cursor = Cursor::new(record_data);
 
     while let Ok(header) = SubRecordHeader::read_le(&mut cursor) {
+        let data_offset = record_data_abs_offset + cursor.position();
         let mut data = vec![0; header.size as usize];
         cursor.read_exact(&mut data)?;
         sub_records
             .entry(header.name)
             .or_insert_with(Vec::new)
-            .push(data);
+            .push((data_offset, data));
     }
 
     Ok(sub_records)
@@ -175,6 +177,8 @@ pub fn extract(
     input_path: &Path,
     output_path: &Path,
     filter_types: Option<&HashSet<String>>,
+    format: Format,
+    tm_path: Option<&Path>,
 ) -> Result<()> {
     println!(
         "Extracting from {} to {}",
@@ -182,8 +186,14 @@ pub fn extract(
         output_path.display()
     );
 
+    let translation_memory = tm_path.map(TranslationMemory::load).transpose()?;
+    if let Some(path) = tm_path {
+        println!("Using translation memory {} for prefill.", path.display());
+    }
+
     let mut file = File::open(input_path)?;
-    let mut wtr = Writer::from_path(output_path)?;
+    let mut writer = OutputWriter::new(output_path, format)?;
+    let index_path = index::index_path_for(output_path);
 
     let tes3_header = RecordHeader::read_le(&mut file)?;
     if tes3_header.name != "TES3" {
@@ -194,7 +204,65 @@ pub fn extract(
     let mut record_count = 0;
     let mut string_count = 0;
 
+    // If a sidecar index from a previous full-scan extract is available, a `--types`
+    // filter can seek straight to matching records instead of scanning the whole file.
+    if let Some(types) = filter_types {
+        if let Some(existing_index) = index::PluginIndex::load(&index_path)? {
+            println!(
+                "Using sidecar index {} to jump to matching records.",
+                index_path.display()
+            );
+            for record in existing_index
+                .records
+                .iter()
+                .filter(|record| types.contains(&record.record_type))
+            {
+                file.seek(SeekFrom::Start(record.record_offset))?;
+                let record_header = RecordHeader::read_le(&mut file)?;
+                let mut record_data = vec![0; record_header.size as usize];
+                file.read_exact(&mut record_data)?;
+                record_count += 1;
+
+                let record_data_abs_offset = record.record_offset + 16;
+                for sub in &record.subrecords {
+                    let rel_offset = (sub.data_offset - record_data_abs_offset) as usize;
+                    let data = &record_data[rel_offset..rel_offset + sub.length as usize];
+                    let original_text = decode_text(data);
+                    let (translated_text, tm_match) =
+                        lookup_tm(translation_memory.as_ref(), &original_text);
+                    writer.write_row(ExtractedRow {
+                        unique_id: &sub.unique_id,
+                        record_type: &record.record_type,
+                        subrecord_type: &sub.subrecord_type,
+                        original_text: &original_text,
+                        original_bytes: data,
+                        translated_text: &translated_text,
+                        tm_match,
+                    })?;
+                    string_count += 1;
+                }
+            }
+
+            writer.finish()?;
+            println!(
+                "Extraction complete. Found {} strings in {} records.",
+                string_count, record_count
+            );
+            return Ok(());
+        }
+    }
+
+    // Full scan: build (or rebuild) the complete index so future filtered extracts of this
+    // plugin can take the fast path above, regardless of the `--types` filter used here.
+    let mut index_builder = index::PluginIndex::default();
+
+    // Guards against two records independently producing the same `unique_id` (e.g. two
+    // GMST records that share the same `NAME` id). Collisions are disambiguated rather than left to
+    // silently overwrite each other in the output.
+    let mut seen_unique_ids: HashSet<String> = HashSet::new();
+
     loop {
+        let record_offset = file.stream_position()?;
         let record_header = match RecordHeader::read_le(&mut file) {
             Ok(h) => h,
             Err(e) if e.is_eof() => break,
@@ -206,28 +274,27 @@ pub fn extract(
         record_count += 1;
 
         let rec_type = &record_header.name;
+        let should_emit = filter_types.is_none_or(|types| types.contains(rec_type));
 
-        if let Some(types) = filter_types {
-            if !types.contains(rec_type) {
-                continue;
-            }
-        }
-
-        if let Some(translatable_fields) = TRANSLATABLE_SUBRECORDS.get(rec_type.as_str()) {
-            let sub_records = parse_subrecords(&record_data)?;
+        let translatable_fields = schema::translatable_fields(rec_type);
+        if !translatable_fields.is_empty() {
+            let record_data_abs_offset = record_offset + 16;
+            let sub_records = parse_subrecords_with_offsets(&record_data, record_data_abs_offset)?;
 
             let object_id = ID_SUBRECORD_CANDIDATES
                 .iter()
                 .find_map(|id_type| sub_records.get(*id_type).and_then(|v| v.first()))
-                .map(|bytes| decode_text(bytes))
+                .map(|(_, bytes)| decode_text(bytes))
                 .unwrap_or_else(|| "UNKNOWN_ID".to_string());
 
-            for sub_rec_type in translatable_fields {
+            let mut subrecord_locations = Vec::new();
+
+            for sub_rec_type in &translatable_fields {
                 if let Some(datas) = sub_records.get(*sub_rec_type) {
-                    for (i, data) in datas.iter().enumerate() {
+                    for (i, (data_offset, data)) in datas.iter().enumerate() {
                         let original_text = decode_text(data);
 
-                        if !is_translatable_text(&original_text) {
+                        if original_text.trim().is_empty() {
                             continue;
                         }
 
@@ -236,24 +303,152 @@ pub fn extract(
                             unique_id.push_str(&format!("_{}", i));
                         }
 
-                        wtr.serialize(CsvRow {
-                            unique_id,
-                            record_type: rec_type,
-                            subrecord_type: sub_rec_type,
-                            original_text,
-                            translated_text: "",
-                        })?;
-                        string_count += 1;
+                        if !seen_unique_ids.insert(unique_id.clone()) {
+                            let base = format!(
+                                "{}~{:08x}",
+                                unique_id,
+                                content_hash(&original_text)
+                            );
+                            // The content hash alone isn't guaranteed unique (two distinct
+                            // duplicates could hash the same, or a third+ occurrence of an
+                            // identical duplicate would collapse onto the second one's id),
+                            // so keep appending an occurrence ordinal until the id is new.
+                            let mut disambiguated = base.clone();
+                            let mut occurrence = 1;
+                            while !seen_unique_ids.insert(disambiguated.clone()) {
+                                occurrence += 1;
+                                disambiguated = format!("{}_{}", base, occurrence);
+                            }
+                            eprintln!(
+                                "Warning: duplicate unique_id '{}'; disambiguating to '{}'",
+                                unique_id, disambiguated
+                            );
+                            unique_id = disambiguated;
+                        }
+
+                        subrecord_locations.push(index::SubrecordLocation {
+                            unique_id: unique_id.clone(),
+                            subrecord_type: (*sub_rec_type).to_string(),
+                            data_offset: *data_offset,
+                            length: data.len() as u32,
+                        });
+
+                        if should_emit {
+                            let (translated_text, tm_match) =
+                                lookup_tm(translation_memory.as_ref(), &original_text);
+                            writer.write_row(ExtractedRow {
+                                unique_id: &unique_id,
+                                record_type: rec_type,
+                                subrecord_type: sub_rec_type,
+                                original_text: &original_text,
+                                original_bytes: data,
+                                translated_text: &translated_text,
+                                tm_match,
+                            })?;
+                            string_count += 1;
+                        }
                     }
                 }
             }
+
+            if !subrecord_locations.is_empty() {
+                index_builder.records.push(index::RecordLocation {
+                    record_type: rec_type.clone(),
+                    record_offset,
+                    subrecords: subrecord_locations,
+                });
+            }
         }
     }
 
-    wtr.flush()?;
+    writer.finish()?;
+    index_builder.save(&index_path)?;
     println!(
         "Extraction complete. Found {} strings in {} records.",
         string_count, record_count
     );
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subrecord(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    fn text_subrecord(name: &str, text: &str) -> Vec<u8> {
+        let mut data = text.as_bytes().to_vec();
+        data.push(0);
+        subrecord(name, &data)
+    }
+
+    fn record(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes()); // unknown
+        bytes.extend_from_slice(&0u32.to_le_bytes()); // flags
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    /// A synthetic TES3 plugin with `count` GMST records that all share the same `NAME` id
+    /// and the same `STRV` text, the scenario that collides into the same base unique_id.
+    fn duplicate_gmst_plugin(object_id: &str, text: &str, count: usize) -> Vec<u8> {
+        let mut tes3_data = Vec::new();
+        tes3_data.extend_from_slice(&subrecord("HEDR", &[0u8; 4]));
+        let mut bytes = record("TES3", &tes3_data);
+
+        for _ in 0..count {
+            let mut record_data = Vec::new();
+            record_data.extend_from_slice(&text_subrecord("NAME", object_id));
+            record_data.extend_from_slice(&text_subrecord("STRV", text));
+            bytes.extend_from_slice(&record("GMST", &record_data));
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn disambiguates_duplicate_unique_ids_past_the_second_occurrence() {
+        let dir = std::env::temp_dir().join(format!(
+            "divay-extractor-test-{}-dup",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input_path = dir.join("plugin.esp");
+        std::fs::write(&input_path, duplicate_gmst_plugin("dup", "Same Text", 3)).unwrap();
+        let output_path = dir.join("strings.csv");
+
+        extract(&input_path, &output_path, None, Format::Csv, None).unwrap();
+
+        #[derive(serde::Deserialize)]
+        struct ReadRow {
+            unique_id: String,
+        }
+
+        let mut rdr = csv::Reader::from_path(&output_path).unwrap();
+        let unique_ids: Vec<String> = rdr
+            .deserialize()
+            .map(|row: Result<ReadRow, _>| row.unwrap().unique_id)
+            .collect();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(unique_ids.len(), 3, "all three duplicate records should still be extracted");
+        let mut deduped = unique_ids.clone();
+        deduped.sort();
+        deduped.dedup();
+        assert_eq!(
+            deduped.len(),
+            3,
+            "every duplicate record must get a distinct unique_id, got {:?}",
+            unique_ids
+        );
+    }
+}
@@ -0,0 +1,10 @@
+use std::hash::{Hash, Hasher};
+
+/// A short, stable hash of `text`, shared by the extractor's duplicate-`unique_id`
+/// disambiguation and the translation memory's string interning, both of which need the same
+/// content text to always map to the same hash across runs.
+pub fn content_hash(text: &str) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
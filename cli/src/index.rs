@@ -0,0 +1,61 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Location of a single translatable subrecord's data within the plugin file, used by
+/// `inject` to seek-overwrite in place when a translation is byte-length-identical.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SubrecordLocation {
+    pub unique_id: String,
+    pub subrecord_type: String,
+    pub data_offset: u64,
+    pub length: u32,
+}
+
+/// Location of a translatable record and its translatable subrecords, used by `extract`
+/// to jump straight to matching records via `--types` without scanning the whole file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecordLocation {
+    pub record_type: String,
+    pub record_offset: u64,
+    pub subrecords: Vec<SubrecordLocation>,
+}
+
+/// Sidecar index of every translatable record/subrecord's byte offsets in a plugin file,
+/// written next to the CSV by `extract` and consumed by both `extract` and `inject`.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct PluginIndex {
+    pub records: Vec<RecordLocation>,
+}
+
+impl PluginIndex {
+    /// Loads the sidecar index at `path`, or returns `None` if it doesn't exist yet.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&contents)?))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    pub fn find_subrecord(&self, unique_id: &str) -> Option<&SubrecordLocation> {
+        self.records
+            .iter()
+            .flat_map(|record| &record.subrecords)
+            .find(|sub| sub.unique_id == unique_id)
+    }
+}
+
+/// Sidecar index path for a given CSV path, e.g. `translations.csv` -> `translations.csv.idx`.
+pub fn index_path_for(csv_path: &Path) -> PathBuf {
+    let mut file_name = csv_path.as_os_str().to_owned();
+    file_name.push(".idx");
+    PathBuf::from(file_name)
+}
@@ -1,10 +1,14 @@
+use crate::index;
+use crate::schema;
+use crate::tm::TranslationMemory;
+use crate::Format;
 use anyhow::Result;
 use binrw::{io::Cursor, BinRead, BinWrite};
 use csv::Reader;
 use encoding_rs::WINDOWS_1252;
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::{Read, Write};
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::Path;
 
 #[derive(BinRead, BinWrite, Debug)]
@@ -36,55 +40,142 @@ struct CsvRow {
     translated_text: String,
 }
 
+/// Mirrors [`crate::extractor`]'s JSON row; only the fields needed to match and
+/// reconstruct a subrecord are declared, extra JSON fields are ignored by serde.
+#[derive(serde::Deserialize)]
+struct JsonRow {
+    unique_id: String,
+    original_bytes_hex: String,
+    translated_text: String,
+}
+
 #[derive(Debug, Clone)]
 struct TranslationEntry {
     original_text: String,
     translated_text: String,
 }
 
+#[derive(Debug, Clone)]
+struct JsonTranslationEntry {
+    original_bytes: Vec<u8>,
+    translated_text: String,
+}
+
+/// Loaded translations, format-specific in how they're matched against a record's current
+/// subrecord bytes: CSV compares trimmed decoded text, JSON compares the raw bytes exactly.
+enum Translations {
+    Csv(HashMap<String, TranslationEntry>),
+    Json(HashMap<String, JsonTranslationEntry>),
+}
+
+impl Translations {
+    fn len(&self) -> usize {
+        match self {
+            Translations::Csv(map) => map.len(),
+            Translations::Json(map) => map.len(),
+        }
+    }
+
+    fn unique_ids(&self) -> HashSet<&str> {
+        match self {
+            Translations::Csv(map) => map.keys().map(String::as_str).collect(),
+            Translations::Json(map) => map.keys().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// Prints a structured end-of-run diagnostic for translations/records that didn't line up,
+/// so a translator can see unmatched or ambiguous entries instead of them being dropped silently.
+fn print_injection_summary(unmatched_subrecord_ids: &[String], unused_translation_ids: &[&str]) {
+    if unmatched_subrecord_ids.is_empty() && unused_translation_ids.is_empty() {
+        return;
+    }
+
+    println!("--- Injection summary ---");
+    if !unmatched_subrecord_ids.is_empty() {
+        println!(
+            "{} translatable subrecord(s) had no matching translation:",
+            unmatched_subrecord_ids.len()
+        );
+        for id in unmatched_subrecord_ids {
+            println!("  - {}", id);
+        }
+    }
+    if !unused_translation_ids.is_empty() {
+        println!(
+            "{} translation entry/entries never matched a record:",
+            unused_translation_ids.len()
+        );
+        for id in unused_translation_ids {
+            println!("  - {}", id);
+        }
+    }
+}
+
+fn load_csv_translations(csv_path: &Path) -> Result<HashMap<String, TranslationEntry>> {
+    let mut translations = HashMap::new();
+    let mut rdr = Reader::from_path(csv_path)?;
+    for result in rdr.deserialize() {
+        let row: CsvRow = result?;
+        if !row.translated_text.trim().is_empty() {
+            translations.insert(
+                row.unique_id,
+                TranslationEntry {
+                    original_text: row.original_text,
+                    translated_text: row.translated_text,
+                },
+            );
+        }
+    }
+    Ok(translations)
+}
+
+fn load_json_translations(json_path: &Path) -> Result<HashMap<String, JsonTranslationEntry>> {
+    let contents = fs::read_to_string(json_path)?;
+    let rows: Vec<JsonRow> = serde_json::from_str(&contents)?;
+    let mut translations = HashMap::new();
+    for row in rows {
+        if !row.translated_text.trim().is_empty() {
+            translations.insert(
+                row.unique_id,
+                JsonTranslationEntry {
+                    original_bytes: from_hex(&row.original_bytes_hex)?,
+                    translated_text: row.translated_text,
+                },
+            );
+        }
+    }
+    Ok(translations)
+}
+
+/// Decodes a lowercase hex string back into raw bytes, the inverse of the extractor's `to_hex`.
+fn from_hex(s: &str) -> Result<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return Err(anyhow::anyhow!("Invalid hex string (odd length): {}", s));
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).map_err(|e| anyhow::anyhow!(e)))
+        .collect()
+}
+
+/// The bytes of `data` following its first NUL terminator, if any. On inject, these are
+/// preserved verbatim so a JSON-format round trip doesn't drop any post-terminator payload.
+fn tail_after_first_nul(data: &[u8]) -> &[u8] {
+    match data.iter().position(|&b| b == 0) {
+        Some(pos) => &data[pos + 1..],
+        None => &[],
+    }
+}
+
+/// Encodes `text` as WINDOWS-1252 without appending a NUL terminator, so callers can append
+/// their own terminator followed by any preserved post-terminator payload.
+fn encode_text_raw(text: &str) -> Vec<u8> {
+    let (encoded, _, _) = WINDOWS_1252.encode(text);
+    encoded.into_owned()
+}
+
 lazy_static::lazy_static! {
-    static ref TRANSLATABLE_SUBRECORDS: HashMap<&'static str, HashSet<&'static str>> = {
-        let mut m = HashMap::new();
-        m.insert("ACTI", ["FNAM"].iter().cloned().collect());
-        m.insert("ALCH", ["FNAM"].iter().cloned().collect());
-        m.insert("APPA", ["FNAM"].iter().cloned().collect());
-        m.insert("ARMO", ["FNAM"].iter().cloned().collect());
-        m.insert("BODY", ["FNAM"].iter().cloned().collect());
-        m.insert("BOOK", ["FNAM", "TEXT"].iter().cloned().collect());
-        m.insert("BSGN", ["FNAM", "DESC"].iter().cloned().collect());
-        m.insert("CLAS", ["FNAM", "DESC"].iter().cloned().collect());
-        m.insert("CLOT", ["FNAM"].iter().cloned().collect());
-        m.insert("CONT", ["FNAM"].iter().cloned().collect());
-        m.insert("CREA", ["FNAM"].iter().cloned().collect());
-        m.insert("DIAL", ["NAME"].iter().cloned().collect());
-        m.insert("DOOR", ["FNAM"].iter().cloned().collect());
-        m.insert("ENCH", ["FNAM"].iter().cloned().collect());
-        m.insert("FACT", ["FNAM"].iter().cloned().collect());
-        m.insert("GLOB", ["FNAM"].iter().cloned().collect());
-        m.insert("GMST", ["STRV"].iter().cloned().collect());
-        m.insert("INFO", ["NAME"].iter().cloned().collect());
-        m.insert("INGR", ["FNAM"].iter().cloned().collect());
-        m.insert("LEVC", ["NNAM"].iter().cloned().collect());
-        m.insert("LEVI", ["NNAM"].iter().cloned().collect());
-        m.insert("LIGH", ["FNAM"].iter().cloned().collect());
-        m.insert("LOCK", ["FNAM"].iter().cloned().collect());
-        m.insert("MGEF", ["DESC"].iter().cloned().collect());
-        m.insert("MISC", ["FNAM"].iter().cloned().collect());
-        m.insert("NPC_", ["FNAM"].iter().cloned().collect());
-        m.insert("PGRD", ["NAME"].iter().cloned().collect());
-        m.insert("PROB", ["FNAM"].iter().cloned().collect());
-        m.insert("RACE", ["FNAM", "DESC"].iter().cloned().collect());
-        m.insert("REGN", ["FNAM"].iter().cloned().collect());
-        m.insert("REPA", ["FNAM"].iter().cloned().collect());
-        m.insert("SKIL", ["DESC"].iter().cloned().collect());
-        m.insert("SNDG", ["FNAM"].iter().cloned().collect());
-        m.insert("SOUN", ["FNAM"].iter().cloned().collect());
-        m.insert("SPEL", ["FNAM"].iter().cloned().collect());
-        m.insert("SSCR", ["NAME"].iter().cloned().collect());
-        m.insert("STAT", ["FNAM"].iter().cloned().collect());
-        m.insert("WEAP", ["FNAM"].iter().cloned().collect());
-        m
-    };
     static ref ID_SUBRECORD_CANDIDATES: Vec<&'static str> = vec!["NAME", "INAM", "CNAM", "BNAM", "ANAM", "NNAM"];
 }
 
@@ -117,6 +208,59 @@ fn parse_subrecords(record_data: &[u8]) -> Result<Vec<(SubRecordHeader, Vec<u8>)
     Ok(sub_records)
 }
 
+/// Like [`parse_subrecords`], but also pairs each subrecord with the byte offset of its data
+/// relative to the start of `record_data`, so callers can resolve it against the sidecar
+/// index's absolute `data_offset`s.
+fn parse_subrecords_with_offsets(record_data: &[u8]) -> Result<Vec<(SubRecordHeader, Vec<u8>, u64)>> {
+    let mut sub_records = Vec::new();
+    let mut cursor = Cursor::new(record_data);
+
+    while let Ok(header) = SubRecordHeader::read_le(&mut cursor) {
+        let rel_offset = cursor.position();
+        let mut data = vec![0; header.size as usize];
+        cursor.read_exact(&mut data)?;
+        sub_records.push((header, data, rel_offset));
+    }
+
+    Ok(sub_records)
+}
+
+/// Flattens the sidecar index into a `(record_offset, data_offset) -> unique_id` lookup, built
+/// once per `inject` call so the full-rewrite loop can resolve every subrecord's id in O(1)
+/// instead of rescanning every indexed record (then every one of its subrecords) per subrecord
+/// processed — a rescan the indexed path exists specifically to avoid.
+fn index_unique_ids(plugin_index: &index::PluginIndex) -> HashMap<(u64, u64), &str> {
+    plugin_index
+        .records
+        .iter()
+        .flat_map(|record| {
+            record.subrecords.iter().map(move |sub| {
+                (
+                    (record.record_offset, sub.data_offset),
+                    sub.unique_id.as_str(),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Resolves a subrecord's authoritative `unique_id` via `index_ids` (see [`index_unique_ids`]),
+/// falling back to `fallback` (the occurrence-count-based derivation also used when extracting
+/// without an index) when no index is available or the offset isn't in it. Preferring the
+/// index avoids the full-rewrite path silently recomputing a different id than the extractor
+/// assigned for a disambiguated duplicate unique_id.
+fn resolve_unique_id(
+    index_ids: Option<&HashMap<(u64, u64), &str>>,
+    record_offset: u64,
+    data_offset: u64,
+    fallback: impl FnOnce() -> String,
+) -> String {
+    index_ids
+        .and_then(|ids| ids.get(&(record_offset, data_offset)))
+        .map(|id| id.to_string())
+        .unwrap_or_else(fallback)
+}
+
 fn rebuild_record_data(sub_records: &Vec<(SubRecordHeader, Vec<u8>)>) -> Result<Vec<u8>> {
     let mut record_data = Vec::new();
     for (sub_header, sub_data) in sub_records.iter() {
@@ -128,11 +272,210 @@ fn rebuild_record_data(sub_records: &Vec<(SubRecordHeader, Vec<u8>)>) -> Result<
     Ok(record_data)
 }
 
+/// Rewrites the `num_records` field at the tail of a `HEDR` subrecord's data.
+///
+/// The HEDR layout is fixed (version: f32, file_type: u32, company_name: [u8; 32],
+/// description: [u8; 256], num_records: u32), so the count is always the last 4 bytes.
+fn patch_hedr_record_count(hedr_data: &[u8], record_count: u32) -> Vec<u8> {
+    let mut patched = hedr_data.to_vec();
+    let len = patched.len();
+    if len >= 4 {
+        patched[len - 4..].copy_from_slice(&record_count.to_le_bytes());
+    }
+    patched
+}
+
+/// Builds a TES3 header body (HEDR + a single MAST/DATA master entry) for a patch plugin
+/// that depends solely on `master_name`, with `record_count` reflecting the emitted records.
+fn build_patch_tes3_data(
+    original_tes3_data: &[u8],
+    master_name: &str,
+    master_size: u64,
+    record_count: u32,
+) -> Result<Vec<u8>> {
+    let sub_records = parse_subrecords(original_tes3_data)?;
+
+    let mut data = Vec::new();
+    for (sub_header, sub_data) in sub_records.iter() {
+        if sub_header.name != "HEDR" {
+            continue;
+        }
+        let patched_data = patch_hedr_record_count(sub_data, record_count);
+        let patched_header = SubRecordHeader {
+            name: sub_header.name.clone(),
+            size: patched_data.len() as u32,
+        };
+        let mut header_bytes = Cursor::new(Vec::new());
+        patched_header.write_le(&mut header_bytes)?;
+        data.extend_from_slice(header_bytes.get_ref());
+        data.extend_from_slice(&patched_data);
+        break;
+    }
+
+    let master_bytes = encode_text(master_name);
+    let mast_header = SubRecordHeader {
+        name: "MAST".to_string(),
+        size: master_bytes.len() as u32,
+    };
+    let mut mast_header_bytes = Cursor::new(Vec::new());
+    mast_header.write_le(&mut mast_header_bytes)?;
+    data.extend_from_slice(mast_header_bytes.get_ref());
+    data.extend_from_slice(&master_bytes);
+
+    let data_header = SubRecordHeader {
+        name: "DATA".to_string(),
+        size: 8,
+    };
+    let mut data_header_bytes = Cursor::new(Vec::new());
+    data_header.write_le(&mut data_header_bytes)?;
+    data.extend_from_slice(data_header_bytes.get_ref());
+    data.extend_from_slice(&master_size.to_le_bytes());
+
+    Ok(data)
+}
+
+/// Copies `input[start..end)` to `output`, overwriting any `patches` (absolute offset, new
+/// bytes) that fall entirely within that range before writing it out. Used to carry forward
+/// same-length in-place subrecord overwrites through the stretches of the file that the
+/// index-accelerated path doesn't need to rebuild.
+fn copy_range_with_patches(
+    input: &mut File,
+    output: &mut File,
+    start: u64,
+    end: u64,
+    patches: &[(u64, Vec<u8>)],
+) -> Result<()> {
+    if end <= start {
+        return Ok(());
+    }
+    let mut buf = vec![0; (end - start) as usize];
+    input.seek(SeekFrom::Start(start))?;
+    input.read_exact(&mut buf)?;
+    for (offset, bytes) in patches {
+        if *offset >= start && *offset + bytes.len() as u64 <= end {
+            let rel = (*offset - start) as usize;
+            buf[rel..rel + bytes.len()].copy_from_slice(bytes);
+        }
+    }
+    output.write_all(&buf)?;
+    Ok(())
+}
+
+/// Rebuilds a single record's data for the index-accelerated path, applying every
+/// translation whose subrecord (resolved via `record_location`, not recomputed occurrence
+/// counts) matches a translation's unique_id and whose original text still matches what's
+/// on disk. Returns the rebuilt data, the resulting size delta, and how many strings changed.
+fn rebuild_indexed_record(
+    record_data: &[u8],
+    record_location: &index::RecordLocation,
+    translation_map: &HashMap<String, TranslationEntry>,
+) -> Result<(Vec<u8>, i32, usize)> {
+    let mut sub_records = parse_subrecords_with_offsets(record_data)?;
+    let base_offset = record_location.record_offset + 16;
+    let mut size_change: i32 = 0;
+    let mut strings_injected = 0;
+
+    for (sub_header, data, rel_offset) in sub_records.iter_mut() {
+        let abs_offset = base_offset + *rel_offset;
+        let Some(location) = record_location
+            .subrecords
+            .iter()
+            .find(|sub| sub.data_offset == abs_offset)
+        else {
+            continue;
+        };
+        let Some(entry) = translation_map.get(&location.unique_id) else {
+            continue;
+        };
+
+        let original_text_in_record = decode_text(data);
+        if original_text_in_record.trim() != entry.original_text.trim() {
+            eprintln!(
+                "Warning: Original text mismatch for {}. Record: '{}', CSV: '{}'",
+                location.unique_id, original_text_in_record, entry.original_text
+            );
+            continue;
+        }
+
+        let new_encoded_text = encode_text(&entry.translated_text);
+        if new_encoded_text != *data {
+            size_change += new_encoded_text.len() as i32 - data.len() as i32;
+            sub_header.size = new_encoded_text.len() as u32;
+            *data = new_encoded_text;
+            strings_injected += 1;
+        }
+    }
+
+    let pairs: Vec<(SubRecordHeader, Vec<u8>)> = sub_records
+        .into_iter()
+        .map(|(header, data, _)| (header, data))
+        .collect();
+    Ok((rebuild_record_data(&pairs)?, size_change, strings_injected))
+}
+
+/// Writes `output_path` by streaming `input_path` through record by record: bytes in between
+/// the records in `records_to_rebuild` are copied straight through (with `in_place_writes`
+/// patched in), and each record in `records_to_rebuild` is fully rebuilt via
+/// [`rebuild_indexed_record`], since its resized subrecord shifts every subsequent byte.
+/// Returns the total number of strings injected (in place plus within rebuilt records).
+fn write_index_accelerated_output(
+    input_path: &Path,
+    output_path: &Path,
+    plugin_index: &index::PluginIndex,
+    translation_map: &HashMap<String, TranslationEntry>,
+    in_place_writes: &[(u64, Vec<u8>)],
+    records_to_rebuild: &HashSet<u64>,
+) -> Result<usize> {
+    let mut input_file = File::open(input_path)?;
+    let mut output_file = File::create(output_path)?;
+    let input_len = input_file.metadata()?.len();
+
+    let mut rebuild_offsets: Vec<u64> = records_to_rebuild.iter().copied().collect();
+    rebuild_offsets.sort_unstable();
+
+    let mut strings_injected = in_place_writes.len();
+    let mut cursor = 0u64;
+    for record_offset in rebuild_offsets {
+        copy_range_with_patches(
+            &mut input_file,
+            &mut output_file,
+            cursor,
+            record_offset,
+            in_place_writes,
+        )?;
+
+        input_file.seek(SeekFrom::Start(record_offset))?;
+        let mut record_header = RecordHeader::read_le(&mut input_file)?;
+        let mut record_data = vec![0; record_header.size as usize];
+        input_file.read_exact(&mut record_data)?;
+
+        let record_location = plugin_index
+            .records
+            .iter()
+            .find(|record| record.record_offset == record_offset)
+            .expect("record_offset came from this index");
+        let (new_record_data, size_change, injected) =
+            rebuild_indexed_record(&record_data, record_location, translation_map)?;
+        strings_injected += injected;
+        record_header.size = (record_header.size as i32 + size_change) as u32;
+        record_header.write_le(&mut output_file)?;
+        output_file.write_all(&new_record_data)?;
+
+        cursor = record_offset + 16 + record_data.len() as u64;
+    }
+
+    copy_range_with_patches(&mut input_file, &mut output_file, cursor, input_len, in_place_writes)?;
+    output_file.flush()?;
+    Ok(strings_injected)
+}
+
 pub fn inject(
     input_path: &Path,
     csv_path: &Path,
     output_path: &Path,
-    _patch_mode: bool,
+    patch_mode: bool,
+    format: Format,
+    tm_path: Option<&Path>,
 ) -> Result<()> {
     println!(
         "Injecting translations from {} in {} to {}",
@@ -141,21 +484,144 @@ pub fn inject(
         output_path.display()
     );
 
-    let mut translations: HashMap<String, TranslationEntry> = HashMap::new();
-    let mut rdr = Reader::from_path(csv_path)?;
-    for result in rdr.deserialize() {
-        let row: CsvRow = result?;
-        if !row.translated_text.trim().is_empty() {
-            translations.insert(
-                row.unique_id,
-                TranslationEntry {
-                    original_text: row.original_text,
-                    translated_text: row.translated_text,
-                },
+    let translations = match format {
+        Format::Csv => Translations::Csv(load_csv_translations(csv_path)?),
+        Format::Json => Translations::Json(load_json_translations(csv_path)?),
+    };
+    println!(
+        "Loaded {} translations from {}.",
+        translations.len(),
+        csv_path.display()
+    );
+
+    if let Some(path) = tm_path {
+        let mut memory = TranslationMemory::load(path)?;
+        match &translations {
+            Translations::Csv(map) => {
+                for entry in map.values() {
+                    memory.insert(&entry.original_text, &entry.translated_text);
+                }
+            }
+            Translations::Json(map) => {
+                for entry in map.values() {
+                    memory.insert(&decode_text(&entry.original_bytes), &entry.translated_text);
+                }
+            }
+        }
+        memory.save(path)?;
+        println!(
+            "Recorded {} translation(s) into translation memory {}.",
+            translations.len(),
+            path.display()
+        );
+    }
+
+    // Also consulted by the full-rewrite path below to resolve disambiguated unique_ids.
+    let plugin_index = index::PluginIndex::load(&index::index_path_for(csv_path))?;
+
+    // Index-accelerated path: a CSV translation whose target subrecord still holds the
+    // original text recorded for it (compared with surrounding whitespace trimmed), and whose re-encoded translation keeps the subrecord's
+    // byte length unchanged, can be overwritten in place without parsing the file at all. A
+    // translation that would resize a subrecord only forces its *containing record* (not the
+    // whole file) through a full parse-and-rebuild, since a resize shifts every subsequent
+    // byte. JSON translations always take the full-rewrite path below instead, since only
+    // that path's tail-preservation logic keeps bytes after a subrecord's NUL terminator
+    // intact.
+    if !patch_mode {
+        if let (Some(plugin_index), Translations::Csv(translation_map)) =
+            (&plugin_index, &translations)
+        {
+            let mut in_place_writes: Vec<(u64, Vec<u8>)> = Vec::new();
+            let mut records_to_rebuild: HashSet<u64> = HashSet::new();
+            let mut matched_ids: HashSet<&str> = HashSet::new();
+
+            let mut source_file = File::open(input_path)?;
+            for (unique_id, entry) in translation_map {
+                let Some(location) = plugin_index.find_subrecord(unique_id) else {
+                    continue;
+                };
+                matched_ids.insert(unique_id.as_str());
+
+                let mut current_bytes = vec![0; location.length as usize];
+                source_file.seek(SeekFrom::Start(location.data_offset))?;
+                source_file.read_exact(&mut current_bytes)?;
+
+                let original_text_on_disk = decode_text(&current_bytes);
+                if original_text_on_disk.trim() != entry.original_text.trim() {
+                    eprintln!(
+                        "Warning: Original text mismatch for {}. Record: '{}', CSV: '{}'",
+                        unique_id, original_text_on_disk, entry.original_text
+                    );
+                    continue;
+                }
+
+                let encoded = encode_text(&entry.translated_text);
+                if encoded == current_bytes {
+                    continue;
+                }
+                if encoded.len() as u32 == location.length {
+                    in_place_writes.push((location.data_offset, encoded));
+                } else {
+                    let record_offset = plugin_index
+                        .records
+                        .iter()
+                        .find(|record| {
+                            record
+                                .subrecords
+                                .iter()
+                                .any(|sub| sub.data_offset == location.data_offset)
+                        })
+                        .map(|record| record.record_offset)
+                        .expect("a located subrecord belongs to some indexed record");
+                    records_to_rebuild.insert(record_offset);
+                }
+            }
+
+            // A rebuilt record re-applies all of its own subrecords' translations together
+            // (see `rebuild_indexed_record`), so drop any same-length patch already queued
+            // for a subrecord that lives inside one of those records.
+            in_place_writes.retain(|(offset, _)| {
+                !plugin_index.records.iter().any(|record| {
+                    records_to_rebuild.contains(&record.record_offset)
+                        && record
+                            .subrecords
+                            .iter()
+                            .any(|sub| sub.data_offset == *offset)
+                })
+            });
+
+            let strings_injected = write_index_accelerated_output(
+                input_path,
+                output_path,
+                plugin_index,
+                translation_map,
+                &in_place_writes,
+                &records_to_rebuild,
+            )?;
+            println!(
+                "Injection complete (index-accelerated). {} string(s) injected ({} record(s) rebuilt for a size change).",
+                strings_injected,
+                records_to_rebuild.len()
             );
+
+            let unmatched_subrecord_ids: Vec<String> = plugin_index
+                .records
+                .iter()
+                .flat_map(|record| &record.subrecords)
+                .map(|sub| sub.unique_id.as_str())
+                .filter(|id| !matched_ids.contains(id))
+                .map(str::to_string)
+                .collect();
+            let unused_translation_ids: Vec<&str> = translation_map
+                .keys()
+                .map(String::as_str)
+                .filter(|id| plugin_index.find_subrecord(id).is_none())
+                .collect();
+            print_injection_summary(&unmatched_subrecord_ids, &unused_translation_ids);
+
+            return Ok(());
         }
     }
-    println!("Loaded {} translations from the CSV.", translations.len());
 
     let mut input_file = File::open(input_path)?;
     let mut output_file = File::create(output_path)?;
@@ -167,13 +633,19 @@ pub fn inject(
     let mut tes3_data = vec![0; tes3_header.size as usize];
     input_file.read_exact(&mut tes3_data)?;
 
-    tes3_header.write_le(&mut output_file)?;
-    output_file.write_all(&tes3_data)?;
+    // Built once up front (instead of per subrecord) so every `resolve_unique_id` call below
+    // is an O(1) lookup — this loop is also the one `--patch` runs, where most records in a
+    // large master end up processed just to be resolved and dropped.
+    let index_unique_id_map = plugin_index.as_ref().map(index_unique_ids);
 
     let mut records_processed = 0;
     let mut strings_injected = 0;
+    let mut emitted_records: Vec<(RecordHeader, Vec<u8>)> = Vec::new();
+    let mut matched_translation_ids: HashSet<String> = HashSet::new();
+    let mut unmatched_subrecord_ids: Vec<String> = Vec::new();
 
     loop {
+        let record_offset = input_file.stream_position()?;
         let mut record_header = match RecordHeader::read_le(&mut input_file) {
             Ok(h) => h,
             Err(e) if e.is_eof() => break,
@@ -186,19 +658,19 @@ pub fn inject(
 
         let rec_type = &record_header.name;
         let original_record_size = record_header.size as i32;
-        let mut new_record_data = record_data.clone();
         let mut current_record_size_change: i32 = 0;
 
-        if let Some(translatable_fields) = TRANSLATABLE_SUBRECORDS.get(rec_type.as_str()) {
-            let mut sub_records = parse_subrecords(&record_data)?;
+        let translatable_fields = schema::translatable_fields(rec_type);
+        if !translatable_fields.is_empty() {
+            let mut sub_records = parse_subrecords_with_offsets(&record_data)?;
 
             let object_id = ID_SUBRECORD_CANDIDATES
                 .iter()
                 .find_map(|id_type| {
                     sub_records
                         .iter()
-                        .find(|(header, _)| &header.name == id_type)
-                        .map(|(_, data)| decode_text(data))
+                        .find(|(header, _, _)| &header.name == id_type)
+                        .map(|(_, data, _)| decode_text(data))
                 })
                 .unwrap_or_else(|| "UNKNOWN_ID".to_string());
 
@@ -206,63 +678,360 @@ pub fn inject(
             let mut sub_record_counts: HashMap<String, usize> = HashMap::new();
             let mut sub_record_occurrence_map: HashMap<String, usize> = HashMap::new();
 
-            for (sub_header, _) in &sub_records {
+            for (sub_header, _, _) in &sub_records {
                 *sub_record_occurrence_map
                     .entry(sub_header.name.clone())
                     .or_insert(0) += 1;
             }
 
-            for (_, (sub_header, data)) in sub_records.iter_mut().enumerate() {
+            for (sub_header, data, rel_offset) in sub_records.iter_mut() {
                 let sub_rec_type = &sub_header.name;
                 let entry_count = sub_record_counts.entry(sub_rec_type.clone()).or_insert(0);
 
                 if translatable_fields.contains(sub_rec_type.as_str()) {
                     let original_text_in_record = decode_text(data);
-                    let mut unique_id = format!("{}|{}|{}", rec_type, object_id, sub_rec_type);
-
                     let num_occurrences =
                         *sub_record_occurrence_map.get(sub_rec_type).unwrap_or(&0);
-                    if num_occurrences > 1 {
-                        unique_id.push_str(&format!("_{}", *entry_count));
-                    }
+                    let abs_data_offset = record_offset + 16 + *rel_offset;
+                    let unique_id = resolve_unique_id(
+                        index_unique_id_map.as_ref(),
+                        record_offset,
+                        abs_data_offset,
+                        || {
+                            let mut id = format!("{}|{}|{}", rec_type, object_id, sub_rec_type);
+                            if num_occurrences > 1 {
+                                id.push_str(&format!("_{}", *entry_count));
+                            }
+                            id
+                        },
+                    );
 
-                    if let Some(translation_entry) = translations.get(&unique_id) {
-                        if original_text_in_record.trim() == translation_entry.original_text.trim()
-                        {
-                            let new_encoded_text = encode_text(&translation_entry.translated_text);
-                            if new_encoded_text != *data {
-                                current_record_size_change +=
-                                    (new_encoded_text.len() as i32) - (data.len() as i32);
-                                sub_header.size = new_encoded_text.len() as u32;
-                                *data = new_encoded_text;
-                                modified = true;
-                                strings_injected += 1;
+                    let mut found_translation = false;
+                    match &translations {
+                        Translations::Csv(map) => {
+                            if let Some(translation_entry) = map.get(&unique_id) {
+                                found_translation = true;
+                                if original_text_in_record.trim()
+                                    == translation_entry.original_text.trim()
+                                {
+                                    let new_encoded_text =
+                                        encode_text(&translation_entry.translated_text);
+                                    if new_encoded_text != *data {
+                                        current_record_size_change +=
+                                            (new_encoded_text.len() as i32) - (data.len() as i32);
+                                        sub_header.size = new_encoded_text.len() as u32;
+                                        *data = new_encoded_text;
+                                        modified = true;
+                                        strings_injected += 1;
+                                    }
+                                } else {
+                                    eprintln!(
+                                        "Warning: Original text mismatch for {}. Record: '{}', CSV: '{}'",
+                                        unique_id, original_text_in_record, translation_entry.original_text
+                                    );
+                                }
                             }
-                        } else {
-                            eprintln!(
-                                "Warning: Original text mismatch for {}. Record: '{}', CSV: '{}'",
-                                unique_id, original_text_in_record, translation_entry.original_text
-                            );
                         }
+                        Translations::Json(map) => {
+                            if let Some(translation_entry) = map.get(&unique_id) {
+                                found_translation = true;
+                                if *data == translation_entry.original_bytes {
+                                    let tail = tail_after_first_nul(data);
+                                    let mut new_encoded_text =
+                                        encode_text_raw(&translation_entry.translated_text);
+                                    new_encoded_text.push(0);
+                                    new_encoded_text.extend_from_slice(tail);
+                                    if new_encoded_text != *data {
+                                        current_record_size_change +=
+                                            (new_encoded_text.len() as i32) - (data.len() as i32);
+                                        sub_header.size = new_encoded_text.len() as u32;
+                                        *data = new_encoded_text;
+                                        modified = true;
+                                        strings_injected += 1;
+                                    }
+                                } else {
+                                    eprintln!(
+                                        "Warning: Original bytes mismatch for {}. The plugin's current bytes no longer match the JSON source.",
+                                        unique_id
+                                    );
+                                }
+                            }
+                        }
+                    }
+
+                    if found_translation {
+                        matched_translation_ids.insert(unique_id.clone());
+                    } else {
+                        unmatched_subrecord_ids.push(unique_id.clone());
                     }
                 }
                 *entry_count += 1;
             }
 
             if modified {
-                new_record_data = rebuild_record_data(&sub_records)?;
+                let pairs: Vec<(SubRecordHeader, Vec<u8>)> = sub_records
+                    .into_iter()
+                    .map(|(header, data, _)| (header, data))
+                    .collect();
+                let new_record_data = rebuild_record_data(&pairs)?;
                 record_header.size = (original_record_size + current_record_size_change) as u32;
+                emitted_records.push((record_header, new_record_data));
+            } else if !patch_mode {
+                // Nothing changed, so the bytes already read in `record_data` are the output
+                // as-is; move them instead of cloning. In patch mode this record is simply
+                // dropped here, which matters on a large master where most records never
+                // change: skipping the clone avoids paying for data that's thrown away anyway.
+                emitted_records.push((record_header, record_data));
             }
+        } else if !patch_mode {
+            emitted_records.push((record_header, record_data));
         }
+    }
 
-        record_header.write_le(&mut output_file)?;
-        output_file.write_all(&new_record_data)?;
+    if patch_mode {
+        let master_name = input_path
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("Input path has no file name"))?
+            .to_string_lossy()
+            .into_owned();
+        let master_size = input_path.metadata()?.len();
+
+        let patch_tes3_data = build_patch_tes3_data(
+            &tes3_data,
+            &master_name,
+            master_size,
+            emitted_records.len() as u32,
+        )?;
+        let patch_tes3_header = RecordHeader {
+            name: "TES3".to_string(),
+            size: patch_tes3_data.len() as u32,
+            unknown: tes3_header.unknown,
+            flags: tes3_header.flags,
+        };
+        patch_tes3_header.write_le(&mut output_file)?;
+        output_file.write_all(&patch_tes3_data)?;
+
+        for (record_header, record_data) in &emitted_records {
+            record_header.write_le(&mut output_file)?;
+            output_file.write_all(record_data)?;
+        }
+
+        output_file.flush()?;
+        println!(
+            "Patch complete. {} strings injected into {} of {} records (patch depends on {}).",
+            strings_injected,
+            emitted_records.len(),
+            records_processed,
+            master_name
+        );
+    } else {
+        tes3_header.write_le(&mut output_file)?;
+        output_file.write_all(&tes3_data)?;
+
+        for (record_header, record_data) in &emitted_records {
+            record_header.write_le(&mut output_file)?;
+            output_file.write_all(record_data)?;
+        }
+
+        output_file.flush()?;
+        println!(
+            "Injection complete. {} strings injected into {} records.",
+            strings_injected, records_processed
+        );
     }
 
-    output_file.flush()?;
-    println!(
-        "Injection complete. {} strings injected into {} records.",
-        strings_injected, records_processed
-    );
+    let all_ids = translations.unique_ids();
+    let unused_translation_ids: Vec<&str> = all_ids
+        .iter()
+        .copied()
+        .filter(|id| !matched_translation_ids.contains(*id))
+        .collect();
+    print_injection_summary(&unmatched_subrecord_ids, &unused_translation_ids);
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subrecord(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    fn text_subrecord(name: &str, text: &str) -> Vec<u8> {
+        let mut data = text.as_bytes().to_vec();
+        data.push(0);
+        subrecord(name, &data)
+    }
+
+    fn record(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes()); // unknown
+        bytes.extend_from_slice(&0u32.to_le_bytes()); // flags
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    /// A minimal synthetic TES3 plugin with one GMST record per `(object_id, text)` pair,
+    /// each holding a `NAME` id subrecord and a translatable `STRV` subrecord.
+    fn gmst_plugin(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut tes3_data = Vec::new();
+        tes3_data.extend_from_slice(&subrecord("HEDR", &[0u8; 4]));
+        let mut bytes = record("TES3", &tes3_data);
+
+        for (object_id, text) in entries {
+            let mut record_data = Vec::new();
+            record_data.extend_from_slice(&text_subrecord("NAME", object_id));
+            record_data.extend_from_slice(&text_subrecord("STRV", text));
+            bytes.extend_from_slice(&record("GMST", &record_data));
+        }
+
+        bytes
+    }
+
+    fn write_csv(path: &Path, rows: &[(&str, &str, &str)]) {
+        let mut contents = String::from("unique_id,original_text,translated_text\n");
+        for (unique_id, original_text, translated_text) in rows {
+            contents.push_str(&format!("{},{},{}\n", unique_id, original_text, translated_text));
+        }
+        fs::write(path, contents).unwrap();
+    }
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("divay-injector-test-{}-{}", std::process::id(), name));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Reads back a plugin built by [`gmst_plugin`] (or a patch built from one), returning
+    /// each GMST record's decoded `STRV` text in file order.
+    fn read_gmst_strv_values(bytes: &[u8]) -> Vec<String> {
+        let mut cursor = Cursor::new(bytes);
+        let tes3_header = RecordHeader::read_le(&mut cursor).unwrap();
+        cursor
+            .seek(SeekFrom::Current(tes3_header.size as i64))
+            .unwrap();
+
+        let mut values = Vec::new();
+        while let Ok(header) = RecordHeader::read_le(&mut cursor) {
+            let mut data = vec![0; header.size as usize];
+            cursor.read_exact(&mut data).unwrap();
+            if header.name == "GMST" {
+                let strv = parse_subrecords(&data)
+                    .unwrap()
+                    .into_iter()
+                    .find(|(h, _)| h.name == "STRV")
+                    .map(|(_, d)| decode_text(&d));
+                if let Some(text) = strv {
+                    values.push(text);
+                }
+            }
+        }
+        values
+    }
+
+    #[test]
+    fn patch_mode_emits_only_modified_records() {
+        let dir = temp_dir("patch");
+        let input_path = dir.join("master.esm");
+        fs::write(
+            &input_path,
+            gmst_plugin(&[("gmst_0", "Value 0"), ("gmst_1", "Value 1"), ("gmst_2", "Value 2")]),
+        )
+        .unwrap();
+
+        let csv_path = dir.join("translations.csv");
+        write_csv(
+            &csv_path,
+            &[("GMST|gmst_1|STRV", "Value 1", "Translated 1")],
+        );
+
+        let output_path = dir.join("patch.esp");
+        inject(&input_path, &csv_path, &output_path, true, Format::Csv, None).unwrap();
+
+        let output = fs::read(&output_path).unwrap();
+        let values = read_gmst_strv_values(&output);
+        fs::remove_dir_all(&dir).ok();
+
+        // Only the one modified record should have been carried into the patch.
+        assert_eq!(values, vec!["Translated 1".to_string()]);
+    }
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Reads back every GMST record's raw `STRV` subrecord bytes, undecoded, so a test can
+    /// assert on bytes after the first NUL terminator as well as the display text itself.
+    fn read_gmst_strv_raw(bytes: &[u8]) -> Vec<Vec<u8>> {
+        let mut cursor = Cursor::new(bytes);
+        let tes3_header = RecordHeader::read_le(&mut cursor).unwrap();
+        cursor
+            .seek(SeekFrom::Current(tes3_header.size as i64))
+            .unwrap();
+
+        let mut values = Vec::new();
+        while let Ok(header) = RecordHeader::read_le(&mut cursor) {
+            let mut data = vec![0; header.size as usize];
+            cursor.read_exact(&mut data).unwrap();
+            if header.name == "GMST" {
+                if let Some((_, strv)) = parse_subrecords(&data)
+                    .unwrap()
+                    .into_iter()
+                    .find(|(h, _)| h.name == "STRV")
+                {
+                    values.push(strv);
+                }
+            }
+        }
+        values
+    }
+
+    #[test]
+    fn json_inject_matches_raw_bytes_and_preserves_tail_after_nul() {
+        let dir = temp_dir("json-tail");
+        let input_path = dir.join("plugin.esp");
+
+        // Bytes after the NUL terminator simulate whatever padding or extra payload the
+        // original plugin's STRV subrecord happened to carry; a correct inject must keep
+        // them verbatim rather than truncating at the terminator like the display text does.
+        let mut original_strv = b"Hello".to_vec();
+        original_strv.push(0);
+        original_strv.extend_from_slice(&[0xAA, 0xBB]);
+
+        let mut record_data = Vec::new();
+        record_data.extend_from_slice(&text_subrecord("NAME", "gmst_0"));
+        record_data.extend_from_slice(&subrecord("STRV", &original_strv));
+        let mut tes3_data = Vec::new();
+        tes3_data.extend_from_slice(&subrecord("HEDR", &[0u8; 4]));
+        let mut bytes = record("TES3", &tes3_data);
+        bytes.extend_from_slice(&record("GMST", &record_data));
+        fs::write(&input_path, bytes).unwrap();
+
+        let json_path = dir.join("translations.json");
+        let json = format!(
+            r#"[{{"unique_id": "GMST|gmst_0|STRV", "original_bytes_hex": "{}", "translated_text": "Bonjour"}}]"#,
+            to_hex(&original_strv)
+        );
+        fs::write(&json_path, json).unwrap();
+
+        let output_path = dir.join("output.esp");
+        inject(&input_path, &json_path, &output_path, false, Format::Json, None).unwrap();
+
+        let output = fs::read(&output_path).unwrap();
+        let values = read_gmst_strv_raw(&output);
+        fs::remove_dir_all(&dir).ok();
+
+        let mut expected = b"Bonjour".to_vec();
+        expected.push(0);
+        expected.extend_from_slice(&[0xAA, 0xBB]);
+        assert_eq!(values, vec![expected]);
+    }
+}
@@ -1,7 +1,22 @@
 use clap::{Parser, Subcommand};
 
 mod extractor;
+mod hash;
+mod index;
 mod injector;
+mod schema;
+mod tm;
+mod verify;
+
+/// On-disk representation for extracted translation data.
+#[derive(Copy, Clone, Debug, clap::ValueEnum)]
+pub enum Format {
+    /// Plain CSV with decoded display text (WINDOWS-1252, truncated at the first NUL).
+    Csv,
+    /// JSON carrying the raw original bytes (hex) alongside the decoded text, for a
+    /// byte-exact round trip through embedded NULs and any bytes after the terminator.
+    Json,
+}
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -18,13 +33,22 @@ enum Commands {
         #[arg(short, long)]
         input: String,
 
-        /// Path to the output .csv file.
+        /// Path to the output file (CSV or JSON, see --format).
         #[arg(short, long)]
         output: String,
 
         /// Comma-separated list of record types to extract (e.g., BOOK,INFO,GMST).
         #[arg(short, long, value_delimiter = ',', value_parser = clap::builder::NonEmptyStringValueParser::new())]
         types: Option<Vec<String>>,
+
+        /// Output format for the translation data.
+        #[arg(long, value_enum, default_value = "csv")]
+        format: Format,
+
+        /// Path to a translation memory file. Strings already approved in a previous
+        /// `inject --tm` run are pre-filled, accumulating across a load order.
+        #[arg(long)]
+        tm: Option<String>,
     },
     /// Injects translated text back into an ESM/ESP file.
     Inject {
@@ -32,7 +56,7 @@ enum Commands {
         #[arg(short, long)]
         input: String,
 
-        /// Path to the .csv file with translations.
+        /// Path to the file with translations (CSV or JSON, see --format).
         #[arg(short, long)]
         csv: String,
 
@@ -43,6 +67,23 @@ enum Commands {
         /// Create a patch ESP instead of a full replacement.
         #[arg(long)]
         patch: bool,
+
+        /// Format of the translations file passed via --csv.
+        #[arg(long, value_enum, default_value = "csv")]
+        format: Format,
+
+        /// Path to a translation memory file. Every row with a non-empty translation in the
+        /// translations file is recorded as an approved translation for reuse by a later
+        /// `extract --tm`, whether or not its original text matched the plugin.
+        #[arg(long)]
+        tm: Option<String>,
+    },
+    /// Round-trips an ESM/ESP through extract + inject (with no translations) and asserts
+    /// the result is byte-for-byte identical to the input.
+    Verify {
+        /// Path to the .esm or .esp file to verify.
+        #[arg(short, long)]
+        input: String,
     },
 }
 
@@ -55,17 +96,37 @@ async fn main() -> anyhow::Result<()> {
             input,
             output,
             types,
+            format,
+            tm,
         } => {
             let filter_types = types.clone().map(|t| t.into_iter().collect());
-            extractor::extract(input.as_ref(), output.as_ref(), filter_types.as_ref())?;
+            extractor::extract(
+                input.as_ref(),
+                output.as_ref(),
+                filter_types.as_ref(),
+                *format,
+                tm.as_ref().map(AsRef::as_ref),
+            )?;
         }
         Commands::Inject {
             input,
             csv,
             output,
             patch,
+            format,
+            tm,
         } => {
-            injector::inject(input.as_ref(), csv.as_ref(), output.as_ref(), *patch)?;
+            injector::inject(
+                input.as_ref(),
+                csv.as_ref(),
+                output.as_ref(),
+                *patch,
+                *format,
+                tm.as_ref().map(AsRef::as_ref),
+            )?;
+        }
+        Commands::Verify { input } => {
+            verify::verify(input.as_ref())?;
         }
     }
     Ok(())
@@ -0,0 +1,70 @@
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+/// The structural kind of a subrecord's payload, used to decide translatability instead of
+/// guessing from the decoded text's shape (script keywords, path separators, and so on).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SubrecordKind {
+    /// Plain display text; translatable just like [`SubrecordKind::LocalizedString`].
+    String,
+    /// Display text shown to the player (item names, book text, descriptions, ...).
+    LocalizedString,
+    /// A file path (mesh, texture, icon, ...); never user-facing text.
+    Path,
+    /// Compiled or source script text; structurally excluded from translation.
+    ScriptSource,
+    /// Opaque binary payload with no textual meaning.
+    Binary,
+    /// A numeric value encoded as text or raw bytes.
+    Numeric,
+}
+
+impl SubrecordKind {
+    fn is_translatable_by_default(self) -> bool {
+        matches!(self, SubrecordKind::String | SubrecordKind::LocalizedString)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubrecordSchema {
+    pub subrecord: String,
+    pub kind: SubrecordKind,
+    /// Overrides the kind's default translatability, e.g. for a script-bearing subrecord
+    /// that would otherwise structurally look like plain text.
+    #[serde(default)]
+    pub translatable: Option<bool>,
+    #[serde(default)]
+    #[allow(dead_code)]
+    pub note: Option<String>,
+}
+
+impl SubrecordSchema {
+    pub fn is_translatable(&self) -> bool {
+        self.translatable
+            .unwrap_or_else(|| self.kind.is_translatable_by_default())
+    }
+}
+
+static SCHEMA_JSON: &str = include_str!("../schema/subrecords.json");
+
+lazy_static::lazy_static! {
+    /// Declarative per-record-type subrecord schema, shipped as `schema/subrecords.json`.
+    static ref SCHEMA: HashMap<String, Vec<SubrecordSchema>> =
+        serde_json::from_str(SCHEMA_JSON).expect("embedded subrecord schema is valid JSON");
+}
+
+/// The translatable subrecord field names for `record_type`, per the schema. Empty if the
+/// record type isn't in the schema or has no translatable fields.
+pub fn translatable_fields(record_type: &str) -> HashSet<&'static str> {
+    SCHEMA
+        .get(record_type)
+        .map(|fields| {
+            fields
+                .iter()
+                .filter(|f| f.is_translatable())
+                .map(|f| f.subrecord.as_str())
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,147 @@
+use crate::hash::content_hash;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// A stable identifier for a source string, derived from its content so the same text
+/// always maps to the same id across plugins, runs, and load orders.
+type StringId = String;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MemoryEntry {
+    pub translated_text: String,
+    /// `false` when the match came from the normalized fuzzy pass rather than an exact
+    /// hit, so callers can flag the prefill as unconfirmed instead of trusting it outright.
+    pub confirmed: bool,
+}
+
+/// Cross-file translation memory: an interning table from decoded source text to a stable
+/// [`StringId`], plus a store of approved translations keyed by that id. Keeping interning
+/// separate from storage means `insert` re-approving the same text just overwrites one
+/// entry instead of accumulating duplicate copies of the source string.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct TranslationMemory {
+    intern: HashMap<String, StringId>,
+    store: HashMap<StringId, MemoryEntry>,
+}
+
+impl TranslationMemory {
+    /// Loads the memory at `path`, or an empty one if it doesn't exist yet (first run).
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    fn id_for(text: &str) -> StringId {
+        format!("{:016x}", content_hash(text))
+    }
+
+    /// Records `translated_text` as the approved translation for `original_text`.
+    pub fn insert(&mut self, original_text: &str, translated_text: &str) {
+        let id = Self::id_for(original_text);
+        self.intern.insert(original_text.to_string(), id.clone());
+        self.store.insert(
+            id,
+            MemoryEntry {
+                translated_text: translated_text.to_string(),
+                confirmed: true,
+            },
+        );
+    }
+
+    /// Looks up a translation for `original_text`: an exact match first, falling back to a
+    /// normalized (trimmed, lowercased) scan of every interned string. A fuzzy match is
+    /// reported with `confirmed: false` since the surrounding whitespace or case may not
+    /// match what was actually approved. When several interned strings normalize to the same
+    /// key, `self.intern`'s `HashMap` iteration order is randomized per process, so candidates
+    /// are collected and sorted by id first to always pick the same one for a given memory file.
+    pub fn lookup(&self, original_text: &str) -> Option<MemoryEntry> {
+        if let Some(id) = self.intern.get(original_text) {
+            if let Some(entry) = self.store.get(id) {
+                return Some(entry.clone());
+            }
+        }
+
+        let normalized = normalize(original_text);
+        let mut candidates: Vec<&StringId> = self
+            .intern
+            .iter()
+            .filter(|(text, _)| normalize(text) == normalized)
+            .map(|(_, id)| id)
+            .collect();
+        candidates.sort();
+
+        candidates
+            .first()
+            .and_then(|id| self.store.get(*id))
+            .map(|entry| MemoryEntry {
+                translated_text: entry.translated_text.clone(),
+                confirmed: false,
+            })
+    }
+}
+
+fn normalize(text: &str) -> String {
+    text.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_match_is_confirmed() {
+        let mut memory = TranslationMemory::default();
+        memory.insert("Hello", "Bonjour");
+
+        let entry = memory.lookup("Hello").expect("exact match should be found");
+        assert_eq!(entry.translated_text, "Bonjour");
+        assert!(entry.confirmed);
+    }
+
+    #[test]
+    fn fuzzy_match_ignores_whitespace_and_case_but_is_unconfirmed() {
+        let mut memory = TranslationMemory::default();
+        memory.insert("Hello", "Bonjour");
+
+        let entry = memory
+            .lookup("  hello  ")
+            .expect("fuzzy match should be found");
+        assert_eq!(entry.translated_text, "Bonjour");
+        assert!(!entry.confirmed);
+    }
+
+    #[test]
+    fn fuzzy_match_tie_break_is_deterministic() {
+        let mut memory = TranslationMemory::default();
+        // Both normalize to "hello", so a lookup that matches neither one exactly must always
+        // resolve to the same one of the two, instead of whatever a HashMap's iteration order
+        // happens to turn up first.
+        memory.insert("Hello", "Bonjour");
+        memory.insert("HELLO", "Salut");
+
+        let id_hello = TranslationMemory::id_for("Hello");
+        let id_upper = TranslationMemory::id_for("HELLO");
+        let expected_translation = if id_hello < id_upper { "Bonjour" } else { "Salut" };
+
+        let entry = memory
+            .lookup("  HELLO  ")
+            .expect("fuzzy match should be found");
+        assert_eq!(entry.translated_text, expected_translation);
+        assert!(!entry.confirmed);
+
+        let entry_again = memory.lookup("  HELLO  ").unwrap();
+        assert_eq!(entry_again.translated_text, expected_translation);
+    }
+}
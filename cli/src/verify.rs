@@ -0,0 +1,195 @@
+use crate::{extractor, index, injector, Format};
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::Path;
+
+/// Extracts every translatable string from `input_path`, injects it straight back with every
+/// translation left blank, and asserts the result is byte-for-byte identical to the original.
+/// A no-op round trip should never change a single byte; any divergence points at a bug in
+/// how `extractor` and `injector` model the file (e.g. a record header length mismatch
+/// between [`extractor::RecordHeader`] and [`injector::RecordHeader`]).
+pub fn verify(input_path: &Path) -> Result<()> {
+    println!("Verifying round trip for {}", input_path.display());
+
+    let file_name = input_path
+        .file_name()
+        .ok_or_else(|| anyhow!("Input path has no file name"))?
+        .to_string_lossy()
+        .into_owned();
+    let work_dir = input_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(format!(".{}.verify", file_name));
+    fs::create_dir_all(&work_dir)?;
+
+    let result = run(input_path, &work_dir);
+    let _ = fs::remove_dir_all(&work_dir);
+    result
+}
+
+fn run(input_path: &Path, work_dir: &Path) -> Result<()> {
+    let csv_path = work_dir.join("strings.csv");
+    let output_path = work_dir.join("roundtrip.esp");
+
+    extractor::extract(input_path, &csv_path, None, Format::Csv, None)?;
+
+    // `inject` takes a seek-and-overwrite shortcut for CSV input whenever a sidecar index is
+    // present, and with an empty translation set that shortcut just copies the input
+    // untouched. That would make this check pass vacuously,
+    // so the index is removed first to force the full parse-and-rewrite path, which is the
+    // one this check exists to exercise.
+    let _ = fs::remove_file(index::index_path_for(&csv_path));
+
+    injector::inject(input_path, &csv_path, &output_path, false, Format::Csv, None)?;
+
+    let original = fs::read(input_path)?;
+    let round_tripped = fs::read(&output_path)?;
+
+    if original.len() != round_tripped.len() {
+        return Err(anyhow!(
+            "Round trip diverges: original is {} bytes, round-tripped is {} bytes",
+            original.len(),
+            round_tripped.len()
+        ));
+    }
+
+    match original
+        .iter()
+        .zip(round_tripped.iter())
+        .position(|(a, b)| a != b)
+    {
+        Some(offset) => Err(anyhow!(
+            "Round trip diverges at {}: original byte 0x{:02x}, round-tripped byte 0x{:02x}",
+            locate(&original, offset),
+            original[offset],
+            round_tripped[offset]
+        )),
+        None => {
+            println!(
+                "Round trip verified: {} is byte-for-byte identical after extract + inject with no translations.",
+                input_path.display()
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Describes `offset` in terms of the record (and, if possible, subrecord) it falls in,
+/// by walking the same 16-byte record / 8-byte subrecord header layout `extractor` and
+/// `injector` use, so a divergence can be pinpointed without a hex-editor session.
+fn locate(data: &[u8], offset: usize) -> String {
+    if data.len() < 16 {
+        return format!("byte offset {}", offset);
+    }
+
+    let tes3_size = u32::from_le_bytes(data[4..8].try_into().unwrap()) as usize;
+    let mut pos = 16 + tes3_size;
+    if offset < pos {
+        return format!("byte offset {} (inside the TES3 header)", offset);
+    }
+
+    let mut record_index = 0;
+    while pos + 16 <= data.len() {
+        let record_type = String::from_utf8_lossy(&data[pos..pos + 4]).into_owned();
+        let size = u32::from_le_bytes(data[pos + 4..pos + 8].try_into().unwrap()) as usize;
+        let data_start = pos + 16;
+        let data_end = (data_start + size).min(data.len());
+
+        if offset < data_end {
+            let subrecord = locate_subrecord(&data[data_start..data_end], offset - data_start);
+            return format!(
+                "byte offset {} (record #{} '{}' at file offset {}{})",
+                offset, record_index, record_type, pos, subrecord
+            );
+        }
+
+        pos = data_start + size;
+        record_index += 1;
+    }
+
+    format!(
+        "byte offset {} (beyond the last record boundary this tool could parse)",
+        offset
+    )
+}
+
+fn locate_subrecord(record_data: &[u8], rel_offset: usize) -> String {
+    let mut pos = 0;
+    while pos + 8 <= record_data.len() {
+        let sub_type = String::from_utf8_lossy(&record_data[pos..pos + 4]).into_owned();
+        let size = u32::from_le_bytes(record_data[pos + 4..pos + 8].try_into().unwrap()) as usize;
+        let data_start = pos + 8;
+        let data_end = (data_start + size).min(record_data.len());
+
+        if rel_offset < data_end {
+            return format!(
+                ", subrecord '{}' at {} bytes into the record",
+                sub_type, pos
+            );
+        }
+
+        pos = data_start + size;
+    }
+    String::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subrecord(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    fn text_subrecord(name: &str, text: &str) -> Vec<u8> {
+        let mut data = text.as_bytes().to_vec();
+        data.push(0);
+        subrecord(name, &data)
+    }
+
+    fn record(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes()); // unknown
+        bytes.extend_from_slice(&0u32.to_le_bytes()); // flags
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    /// Builds a minimal synthetic TES3 plugin with `record_count` GMST records, each holding
+    /// a distinct translatable STRV string. Several sequential records are what exposed the
+    /// 8-byte-vs-16-byte record header mismatch this module's `verify` doc comment describes:
+    /// with the wrong header size every record after the first is read from a desynced offset.
+    fn synthetic_plugin(record_count: usize) -> Vec<u8> {
+        let mut tes3_data = Vec::new();
+        tes3_data.extend_from_slice(&subrecord("HEDR", &[0u8; 4]));
+        let mut bytes = record("TES3", &tes3_data);
+
+        for i in 0..record_count {
+            let mut record_data = Vec::new();
+            record_data.extend_from_slice(&text_subrecord("NAME", &format!("gmst_{}", i)));
+            record_data.extend_from_slice(&text_subrecord("STRV", &format!("Value {}", i)));
+            bytes.extend_from_slice(&record("GMST", &record_data));
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn verifies_multi_record_plugin_round_trip() {
+        let dir = std::env::temp_dir().join(format!("divay-verify-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input_path = dir.join("synthetic.esp");
+        std::fs::write(&input_path, synthetic_plugin(3)).unwrap();
+
+        let result = verify(&input_path);
+
+        std::fs::remove_dir_all(&dir).ok();
+        result.expect("a synthetic multi-record plugin should round-trip byte-for-byte");
+    }
+}